@@ -0,0 +1,251 @@
+use std::cell::RefCell;
+
+use regex::Regex;
+
+pub trait PrivCmp {
+    fn cmp_bool(&self, v1: &bool, v2: &bool) -> bool;
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool;
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool;
+}
+
+pub struct CmpEq;
+pub struct CmpNe;
+pub struct CmpGt;
+pub struct CmpGe;
+pub struct CmpLt;
+pub struct CmpLe;
+
+impl PrivCmp for CmpEq {
+    fn cmp_bool(&self, v1: &bool, v2: &bool) -> bool {
+        v1 == v2
+    }
+
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool {
+        v1 == v2
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        v1 == v2
+    }
+}
+
+impl PrivCmp for CmpNe {
+    fn cmp_bool(&self, v1: &bool, v2: &bool) -> bool {
+        v1 != v2
+    }
+
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool {
+        v1 != v2
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        v1 != v2
+    }
+}
+
+impl PrivCmp for CmpGt {
+    fn cmp_bool(&self, _v1: &bool, _v2: &bool) -> bool {
+        false
+    }
+
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool {
+        v1 > v2
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        v1 > v2
+    }
+}
+
+impl PrivCmp for CmpGe {
+    fn cmp_bool(&self, _v1: &bool, _v2: &bool) -> bool {
+        false
+    }
+
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool {
+        v1 >= v2
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        v1 >= v2
+    }
+}
+
+impl PrivCmp for CmpLt {
+    fn cmp_bool(&self, _v1: &bool, _v2: &bool) -> bool {
+        false
+    }
+
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool {
+        v1 < v2
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        v1 < v2
+    }
+}
+
+impl PrivCmp for CmpLe {
+    fn cmp_bool(&self, _v1: &bool, _v2: &bool) -> bool {
+        false
+    }
+
+    fn cmp_f64(&self, v1: &f64, v2: &f64) -> bool {
+        v1 <= v2
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        v1 <= v2
+    }
+}
+
+pub struct CmpRegex {
+    cache: RefCell<Option<(String, Regex)>>,
+}
+
+impl CmpRegex {
+    pub fn new() -> Self {
+        CmpRegex { cache: RefCell::new(None) }
+    }
+}
+
+impl Default for CmpRegex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrivCmp for CmpRegex {
+    fn cmp_bool(&self, _v1: &bool, _v2: &bool) -> bool {
+        false
+    }
+
+    fn cmp_f64(&self, _v1: &f64, _v2: &f64) -> bool {
+        false
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        let mut cache = self.cache.borrow_mut();
+        let stale = match &*cache {
+            Some((pattern, _)) => pattern != v2,
+            None => true
+        };
+        if stale {
+            match Regex::new(v2) {
+                Ok(re) => *cache = Some((v2.to_owned(), re)),
+                Err(_) => return false
+            }
+        }
+        cache.as_ref().map(|(_, re)| re.is_match(v1)).unwrap_or(false)
+    }
+}
+
+pub struct CmpFuzzy;
+
+impl CmpFuzzy {
+    fn threshold(term: &str) -> usize {
+        if term.chars().count() < 5 { 1 } else { 2 }
+    }
+
+    fn levenshtein(a: &str, b: &str) -> usize {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let (m, n) = (a.len(), b.len());
+
+        let mut prev: Vec<usize> = (0..=n).collect();
+        let mut curr: Vec<usize> = vec![0; n + 1];
+
+        for i in 1..=m {
+            curr[0] = i;
+            for j in 1..=n {
+                let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+                curr[j] = (prev[j] + 1)
+                    .min(curr[j - 1] + 1)
+                    .min(prev[j - 1] + cost);
+            }
+            std::mem::swap(&mut prev, &mut curr);
+        }
+
+        prev[n]
+    }
+}
+
+impl PrivCmp for CmpFuzzy {
+    fn cmp_bool(&self, _v1: &bool, _v2: &bool) -> bool {
+        false
+    }
+
+    fn cmp_f64(&self, _v1: &f64, _v2: &f64) -> bool {
+        false
+    }
+
+    fn cmp_string(&self, v1: &str, v2: &str) -> bool {
+        let threshold = Self::threshold(v2);
+        let len_diff = (v1.chars().count() as isize - v2.chars().count() as isize).unsigned_abs();
+        if len_diff > threshold {
+            return false;
+        }
+        Self::levenshtein(v1, v2) <= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CmpFuzzy, CmpRegex, PrivCmp};
+
+    #[test]
+    fn regex_matches_when_pattern_found_in_string() {
+        let cmp = CmpRegex::new();
+        assert!(cmp.cmp_string("foobar", "^foo"));
+    }
+
+    #[test]
+    fn regex_rejects_when_pattern_not_found_in_string() {
+        let cmp = CmpRegex::new();
+        assert!(!cmp.cmp_string("foobar", "^bar"));
+    }
+
+    #[test]
+    fn regex_rejects_invalid_pattern_instead_of_panicking() {
+        let cmp = CmpRegex::new();
+        assert!(!cmp.cmp_string("foobar", "("));
+    }
+
+    #[test]
+    fn regex_cache_recompiles_for_a_different_pattern() {
+        let cmp = CmpRegex::new();
+        assert!(cmp.cmp_string("foobar", "^foo"));
+        assert!(!cmp.cmp_string("foobar", "^bar"));
+        assert!(cmp.cmp_string("barfoo", "^bar"));
+    }
+
+    #[test]
+    fn fuzzy_matches_within_threshold_for_long_term() {
+        let cmp = CmpFuzzy;
+        assert!(cmp.cmp_string("mountain", "montain"));
+    }
+
+    #[test]
+    fn fuzzy_rejects_beyond_threshold_for_short_term() {
+        let cmp = CmpFuzzy;
+        assert!(!cmp.cmp_string("big", "cat"));
+    }
+
+    #[test]
+    fn fuzzy_allows_exactly_one_edit_for_short_term() {
+        let cmp = CmpFuzzy;
+        assert!(cmp.cmp_string("bat", "cat"));
+    }
+
+    #[test]
+    fn fuzzy_short_circuits_on_length_difference_without_scanning() {
+        let cmp = CmpFuzzy;
+        assert!(!cmp.cmp_string("abcdefgh", "cat"));
+    }
+
+    #[test]
+    fn fuzzy_compares_by_unicode_scalar_not_byte() {
+        let cmp = CmpFuzzy;
+        assert!(cmp.cmp_string("café", "cafe"));
+    }
+}
@@ -0,0 +1,11 @@
+use super::term::{CmpType, ExprTerm};
+use super::value_filter::ValueFilterKey;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    Recursive,
+    Filter(Option<ValueFilterKey>, CmpType, ExprTerm),
+}
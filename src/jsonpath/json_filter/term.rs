@@ -0,0 +1,27 @@
+use super::value_filter::ValueFilterKey;
+use super::value_wrapper::ValueWrapper;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpType {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Regex,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprTerm {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+#[derive(Debug)]
+pub enum TermContext {
+    Constants(ExprTerm),
+    Json(Option<ValueFilterKey>, ValueWrapper),
+}
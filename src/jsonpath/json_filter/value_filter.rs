@@ -0,0 +1,5 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueFilterKey {
+    String(String),
+    All,
+}
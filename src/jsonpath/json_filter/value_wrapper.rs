@@ -1,7 +1,11 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
 use serde_json::Value;
 use indexmap::map::IndexMap;
 
 use super::cmp::*;
+use super::path::*;
 use super::term::*;
 use super::value_filter::*;
 
@@ -28,13 +32,59 @@ impl ValueWrapper {
             CmpType::Ne => {
                 TermContext::Json(None, self.except(other))
             }
-            CmpType::Gt | CmpType::Ge | CmpType::Lt | CmpType::Le => {
-                TermContext::Constants(ExprTerm::Bool(false))
+            _ => self.cmp_elements(other, Self::make_cmp(&cmp_type).as_ref()),
+        }
+    }
+
+    fn make_cmp(cmp_type: &CmpType) -> Box<dyn PrivCmp> {
+        match cmp_type {
+            CmpType::Eq => Box::new(CmpEq),
+            CmpType::Ne => Box::new(CmpNe),
+            CmpType::Gt => Box::new(CmpGt),
+            CmpType::Ge => Box::new(CmpGe),
+            CmpType::Lt => Box::new(CmpLt),
+            CmpType::Le => Box::new(CmpLe),
+            CmpType::Regex => Box::new(CmpRegex::new()),
+            CmpType::Fuzzy => Box::new(CmpFuzzy),
+        }
+    }
+
+    fn element_matches<F: PrivCmp + ?Sized>(v1: &Value, v2: &Value, cmp_fn: &F) -> bool {
+        match (v1, v2) {
+            (Value::Bool(b1), Value::Bool(b2)) => cmp_fn.cmp_bool(b1, b2),
+            (Value::Number(n1), Value::Number(n2)) => {
+                match (n1.as_f64(), n2.as_f64()) {
+                    (Some(ref a), Some(ref b)) => cmp_fn.cmp_f64(a, b),
+                    _ => false
+                }
             }
+            (Value::String(s1), Value::String(s2)) => cmp_fn.cmp_string(s1, s2),
+            _ => false
         }
     }
 
-    fn cmp_with_term<F: PrivCmp>(val: &Value, et: &ExprTerm, cmp_fn: &F, default: bool) -> bool {
+    fn cmp_elements<F: PrivCmp + ?Sized>(&mut self, other: &mut ValueWrapper, cmp_fn: &F) -> TermContext {
+        let rhs = other.val.take();
+        let vw = match self.val.take() {
+            Value::Array(mut vec) => {
+                let ret: Vec<Value> = vec.iter_mut()
+                    .filter(|v| Self::element_matches(v, &rhs, cmp_fn))
+                    .map(|v| v.take())
+                    .collect();
+                ValueWrapper::new(Value::Array(ret), false)
+            }
+            scalar => {
+                if Self::element_matches(&scalar, &rhs, cmp_fn) {
+                    ValueWrapper::new(scalar, false)
+                } else {
+                    ValueWrapper::new(Value::Null, false)
+                }
+            }
+        };
+        TermContext::Json(None, vw)
+    }
+
+    fn cmp_with_term<F: PrivCmp + ?Sized>(val: &Value, et: &ExprTerm, cmp_fn: &F, default: bool) -> bool {
         match val {
             Value::Bool(ref v1) => {
                 match et {
@@ -122,14 +172,18 @@ impl ValueWrapper {
     }
 
     pub fn replace(&mut self, val: Value) {
-        let is_null = match &val {
-            Value::Array(v) => if v.is_empty() { true } else { false },
-            Value::Object(m) => if m.is_empty() { true } else { false },
-            _ => val.is_null()
-        };
+        let is_null = Self::is_emptyish(&val);
         self.val = if is_null { Value::Null } else { val };
     }
 
+    fn is_emptyish(val: &Value) -> bool {
+        match val {
+            Value::Array(v) => v.is_empty(),
+            Value::Object(m) => m.is_empty(),
+            _ => val.is_null()
+        }
+    }
+
     pub fn get_val(&self) -> &Value {
         &self.val
     }
@@ -138,6 +192,141 @@ impl ValueWrapper {
         &mut self.val
     }
 
+    pub fn map_at<F: FnMut(&mut Value)>(&mut self, path: &[PathSegment], f: &mut F) {
+        Self::map_in(&mut self.val, path, f);
+    }
+
+    fn map_in<F: FnMut(&mut Value)>(val: &mut Value, path: &[PathSegment], f: &mut F) {
+        match path.split_first() {
+            None => f(val),
+            Some((PathSegment::Key(key), rest)) => {
+                if let Value::Object(map) = val {
+                    if let Some(child) = map.get_mut(key) {
+                        Self::map_in(child, rest, f);
+                    }
+                }
+            }
+            Some((PathSegment::Index(i), rest)) => {
+                if let Value::Array(vec) = val {
+                    if let Some(child) = vec.get_mut(*i) {
+                        Self::map_in(child, rest, f);
+                    }
+                }
+            }
+            Some((PathSegment::Wildcard, rest)) => match val {
+                Value::Array(vec) => {
+                    for child in vec.iter_mut() {
+                        Self::map_in(child, rest, f);
+                    }
+                }
+                Value::Object(map) => {
+                    for child in map.values_mut() {
+                        Self::map_in(child, rest, f);
+                    }
+                }
+                _ => {}
+            }
+            Some((PathSegment::Recursive, rest)) => {
+                Self::map_in(val, rest, f);
+                match val {
+                    Value::Array(vec) => {
+                        for child in vec.iter_mut() {
+                            Self::map_in(child, path, f);
+                        }
+                    }
+                    Value::Object(map) => {
+                        for child in map.values_mut() {
+                            Self::map_in(child, path, f);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some((PathSegment::Filter(key, cmp_type, term), rest)) => {
+                let cmp_fn = Self::make_cmp(cmp_type);
+                match val {
+                    Value::Array(vec) => {
+                        for child in vec.iter_mut() {
+                            if Self::filter_matches(child, key, term, cmp_fn.as_ref()) {
+                                Self::map_in(child, rest, f);
+                            }
+                        }
+                    }
+                    Value::Object(map) => {
+                        for child in map.values_mut() {
+                            if Self::filter_matches(child, key, term, cmp_fn.as_ref()) {
+                                Self::map_in(child, rest, f);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn filter_matches(val: &Value, key: &Option<ValueFilterKey>, term: &ExprTerm, cmp_fn: &dyn PrivCmp) -> bool {
+        let target = match key {
+            Some(ValueFilterKey::String(key)) => match val {
+                Value::Object(map) => match map.get(key) {
+                    Some(v) => v,
+                    None => return false
+                },
+                _ => return false
+            },
+            _ => val
+        };
+
+        Self::cmp_with_term(target, term, cmp_fn, false)
+    }
+
+    pub fn set_at(&mut self, path: &[PathSegment], new_value: Value) {
+        self.map_at(path, &mut |v| *v = new_value.clone());
+    }
+
+    pub fn delete_at(&mut self, path: &[PathSegment]) {
+        Self::delete_in(&mut self.val, path);
+    }
+
+    fn delete_in(val: &mut Value, path: &[PathSegment]) {
+        let (last, init) = match path.split_last() {
+            Some(split) => split,
+            None => {
+                *val = Value::Null;
+                return;
+            }
+        };
+
+        Self::map_in(val, init, &mut |parent| {
+            match (last, &mut *parent) {
+                (PathSegment::Key(key), Value::Object(map)) => {
+                    map.remove(key);
+                }
+                (PathSegment::Index(i), Value::Array(vec)) => {
+                    if *i < vec.len() {
+                        vec.remove(*i);
+                    }
+                }
+                (PathSegment::Wildcard, Value::Array(vec))
+                | (PathSegment::Recursive, Value::Array(vec)) => vec.clear(),
+                (PathSegment::Wildcard, Value::Object(map))
+                | (PathSegment::Recursive, Value::Object(map)) => map.clear(),
+                (PathSegment::Filter(key, cmp_type, term), Value::Array(vec)) => {
+                    let cmp_fn = Self::make_cmp(cmp_type);
+                    vec.retain(|v| !Self::filter_matches(v, key, term, cmp_fn.as_ref()));
+                }
+                (PathSegment::Filter(key, cmp_type, term), Value::Object(map)) => {
+                    let cmp_fn = Self::make_cmp(cmp_type);
+                    map.retain(|_, v| !Self::filter_matches(v, key, term, cmp_fn.as_ref()));
+                }
+                _ => {}
+            }
+            if Self::is_emptyish(parent) {
+                *parent = Value::Null;
+            }
+        });
+    }
+
     pub fn clone_val(&self) -> Value {
         self.val.clone()
     }
@@ -146,36 +335,62 @@ impl ValueWrapper {
         self.val.is_array()
     }
 
-    fn uuid(v: &Value) -> String {
-        fn _fn(v: &Value) -> String {
+    fn identity(v: &Value) -> u64 {
+        fn hash_value<H: Hasher>(v: &Value, state: &mut H) {
             match v {
-                Value::Null => "null".to_string(),
-                Value::String(v) => v.to_string(),
-                Value::Bool(v) => v.to_string(),
-                Value::Number(v) => v.to_string(),
+                Value::Null => 0u8.hash(state),
+                Value::Bool(v) => {
+                    1u8.hash(state);
+                    v.hash(state);
+                }
+                Value::Number(v) => {
+                    2u8.hash(state);
+                    match v.as_f64() {
+                        Some(v) => v.to_bits().hash(state),
+                        None => v.to_string().hash(state)
+                    }
+                }
+                Value::String(v) => {
+                    3u8.hash(state);
+                    v.len().hash(state);
+                    v.hash(state);
+                }
                 Value::Array(v) => {
-                    v.iter().enumerate()
-                        .map(|(i, v)| { format!("{}{}", i, _fn(v)) })
-                        .collect()
+                    4u8.hash(state);
+                    v.len().hash(state);
+                    for item in v {
+                        hash_value(item, state);
+                    }
                 }
                 Value::Object(v) => {
-                    v.into_iter().map(|(k, v)| { format!("{}{}", k, _fn(v)) }).collect()
+                    5u8.hash(state);
+                    v.len().hash(state);
+                    let mut keys: Vec<&String> = v.keys().collect();
+                    keys.sort();
+                    for k in keys {
+                        k.len().hash(state);
+                        k.hash(state);
+                        hash_value(&v[k], state);
+                    }
                 }
             }
         }
-        _fn(v)
+
+        let mut hasher = DefaultHasher::new();
+        hash_value(v, &mut hasher);
+        hasher.finish()
     }
 
-    fn into_map(&mut self) -> IndexMap<String, Value> {
+    fn into_map(&mut self) -> IndexMap<u64, Value> {
         let mut map = IndexMap::new();
         match &mut self.val {
             Value::Array(v1) => {
                 for v in v1 {
-                    map.insert(Self::uuid(v), v.take());
+                    map.insert(Self::identity(v), v.take());
                 }
             }
             other => {
-                map.insert(Self::uuid(other), other.take());
+                map.insert(Self::identity(other), other.take());
             }
         }
         map
@@ -183,18 +398,18 @@ impl ValueWrapper {
 
     pub fn except(&mut self, other: &mut Self) -> Self {
         let map = self.into_map();
-        let mut ret: IndexMap<String, Value> = IndexMap::new();
+        let mut ret: IndexMap<u64, Value> = IndexMap::new();
         match &mut other.val {
             Value::Array(v1) => {
                 for v in v1 {
-                    let key = Self::uuid(v);
+                    let key = Self::identity(v);
                     if !map.contains_key(&key) {
                         ret.insert(key, v.take());
                     }
                 }
             }
             other => {
-                let key = Self::uuid(other);
+                let key = Self::identity(other);
                 if !map.contains_key(&key) {
                     ret.insert(key, other.take());
                 }
@@ -207,18 +422,18 @@ impl ValueWrapper {
 
     pub fn intersect(&mut self, other: &mut Self) -> Self {
         let map = self.into_map();
-        let mut ret: IndexMap<String, Value> = IndexMap::new();
+        let mut ret: IndexMap<u64, Value> = IndexMap::new();
         match &mut other.val {
             Value::Array(v1) => {
                 for v in v1 {
-                    let key = Self::uuid(v);
+                    let key = Self::identity(v);
                     if map.contains_key(&key) {
                         ret.insert(key, v.take());
                     }
                 }
             }
             other => {
-                let key = Self::uuid(other);
+                let key = Self::identity(other);
                 if map.contains_key(&key) {
                     ret.insert(key, other.take());
                 }
@@ -234,14 +449,14 @@ impl ValueWrapper {
         match &mut other.val {
             Value::Array(v1) => {
                 for v in v1 {
-                    let key = Self::uuid(v);
+                    let key = Self::identity(v);
                     if !map.contains_key(&key) {
                         map.insert(key, v.take());
                     }
                 }
             }
             other => {
-                let key = Self::uuid(other);
+                let key = Self::identity(other);
                 if !map.contains_key(&key) {
                     map.insert(key, other.take());
                 }
@@ -253,4 +468,146 @@ impl ValueWrapper {
         vw.replace(Value::Array(list));
         vw
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::CmpType;
+    use super::ExprTerm;
+    use super::PathSegment;
+    use super::TermContext;
+    use super::ValueFilterKey;
+    use super::ValueWrapper;
+
+    #[test]
+    fn cmp_gt_filters_array_elements_greater_than_rhs() {
+        let mut a = ValueWrapper::new(json!([5, 15, 10]), false);
+        let mut b = ValueWrapper::new(json!(10), false);
+
+        let result = a.cmp(&mut b, CmpType::Gt);
+
+        match result {
+            TermContext::Json(None, vw) => assert_eq!(vw.get_val(), &json!([15])),
+            other => panic!("expected TermContext::Json, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cmp_gt_on_scalar_returns_value_or_null() {
+        let mut a = ValueWrapper::new(json!(15), false);
+        let mut b = ValueWrapper::new(json!(10), false);
+        match a.cmp(&mut b, CmpType::Gt) {
+            TermContext::Json(None, vw) => assert_eq!(vw.get_val(), &json!(15)),
+            other => panic!("expected TermContext::Json, got {:?}", other),
+        }
+
+        let mut a = ValueWrapper::new(json!(5), false);
+        let mut b = ValueWrapper::new(json!(10), false);
+        match a.cmp(&mut b, CmpType::Gt) {
+            TermContext::Json(None, vw) => assert_eq!(vw.get_val(), &json!(null)),
+            other => panic!("expected TermContext::Json, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn intersect_does_not_collapse_object_key_boundaries() {
+        let mut a = ValueWrapper::new(json!([{"a": "1", "b": ""}]), false);
+        let mut b = ValueWrapper::new(json!([{"a": "", "b": "1"}]), false);
+
+        let result = a.intersect(&mut b);
+        assert_eq!(result.get_val(), &json!([]));
+    }
+
+    #[test]
+    fn except_does_not_collapse_array_element_boundaries() {
+        let mut a = ValueWrapper::new(json!(["1", "0"]), false);
+        let mut b = ValueWrapper::new(json!(["10"]), false);
+
+        let result = a.except(&mut b);
+        assert_eq!(result.get_val(), &json!(["10"]));
+    }
+
+    #[test]
+    fn union_keeps_first_insertion_order_and_dedups_true_duplicates() {
+        let mut a = ValueWrapper::new(json!([{"a": "1", "b": ""}, "x"]), false);
+        let mut b = ValueWrapper::new(json!([{"a": "", "b": "1"}, "x"]), false);
+
+        let result = a.union(&mut b);
+        assert_eq!(
+            result.get_val(),
+            &json!([{"a": "1", "b": ""}, "x", {"a": "", "b": "1"}])
+        );
+    }
+
+    #[test]
+    fn set_at_overwrites_every_wildcard_match() {
+        let mut vw = ValueWrapper::new(json!({"items": [{"n": 1}, {"n": 2}]}), false);
+        let path = [
+            PathSegment::Key("items".to_string()),
+            PathSegment::Wildcard,
+            PathSegment::Key("n".to_string()),
+        ];
+
+        vw.set_at(&path, json!(0));
+
+        assert_eq!(vw.get_val(), &json!({"items": [{"n": 0}, {"n": 0}]}));
+    }
+
+    #[test]
+    fn delete_at_removes_indexed_element() {
+        let mut vw = ValueWrapper::new(json!({"items": [1, 2, 3]}), false);
+        let path = [PathSegment::Key("items".to_string()), PathSegment::Index(1)];
+
+        vw.delete_at(&path);
+
+        assert_eq!(vw.get_val(), &json!({"items": [1, 3]}));
+    }
+
+    #[test]
+    fn delete_at_collapses_emptied_parent_to_null() {
+        let mut vw = ValueWrapper::new(json!({"items": [1]}), false);
+        let path = [PathSegment::Key("items".to_string()), PathSegment::Index(0)];
+
+        vw.delete_at(&path);
+
+        assert_eq!(vw.get_val(), &json!({"items": null}));
+    }
+
+    #[test]
+    fn set_at_recursive_descends_to_every_depth() {
+        let mut vw = ValueWrapper::new(
+            json!({"a": {"n": 1, "b": {"n": 2}}, "n": 3}),
+            false,
+        );
+        let path = [PathSegment::Recursive, PathSegment::Key("n".to_string())];
+
+        vw.set_at(&path, json!(0));
+
+        assert_eq!(
+            vw.get_val(),
+            &json!({"a": {"n": 0, "b": {"n": 0}}, "n": 0})
+        );
+    }
+
+    #[test]
+    fn delete_at_filter_removes_only_matching_elements() {
+        let mut vw = ValueWrapper::new(
+            json!({"items": [{"price": 5}, {"price": 15}]}),
+            false,
+        );
+        let path = [
+            PathSegment::Key("items".to_string()),
+            PathSegment::Filter(
+                Some(ValueFilterKey::String("price".to_string())),
+                CmpType::Gt,
+                ExprTerm::Number(10.0),
+            ),
+        ];
+
+        vw.delete_at(&path);
+
+        assert_eq!(vw.get_val(), &json!({"items": [{"price": 5}]}));
+    }
 }
\ No newline at end of file